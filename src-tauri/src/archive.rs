@@ -0,0 +1,376 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{TreeNode, LARGE_FILE_THRESHOLD};
+
+/// Archive formats that [`crate::commands::list_tree`] and
+/// [`crate::commands::read_text_file`] will browse transparently, as if they were
+/// directories on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    pub fn for_name(name: &str) -> Option<ArchiveKind> {
+        let lower = name.to_lowercase();
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if lower.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if lower.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+struct RawEntry {
+    path: String,
+    is_dir: bool,
+    size: u64,
+}
+
+fn reject_unsafe_member(path: &str) -> Result<(), String> {
+    if path.starts_with('/') || path.split('/').any(|part| part == "..") {
+        return Err(format!("Archive member escapes the archive root: {}", path));
+    }
+    Ok(())
+}
+
+fn list_raw_entries(archive_path: &Path, kind: ArchiveKind) -> Result<Vec<RawEntry>, String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+
+    let mut entries = Vec::new();
+
+    match kind {
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader: Box<dyn Read> = if kind == ArchiveKind::TarGz {
+                Box::new(flate2::read::GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive
+                .entries()
+                .map_err(|e| format!("Failed to read archive entries: {}", e))?
+            {
+                let entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+                let path = entry
+                    .path()
+                    .map_err(|e| format!("Invalid archive entry path: {}", e))?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                reject_unsafe_member(&path)?;
+                entries.push(RawEntry {
+                    is_dir: entry.header().entry_type().is_dir(),
+                    size: entry.header().size().unwrap_or(0),
+                    path,
+                });
+            }
+        }
+        ArchiveKind::Zip => {
+            let mut archive =
+                zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip: {}", e))?;
+            for i in 0..archive.len() {
+                let entry = archive
+                    .by_index(i)
+                    .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+                let path = entry.name().replace('\\', "/");
+                reject_unsafe_member(&path)?;
+                entries.push(RawEntry {
+                    is_dir: entry.is_dir(),
+                    size: entry.size(),
+                    path,
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Synthesizes the virtual directory tree for one archive, rooted at `archive_node_name`
+/// (e.g. `logs.tar.gz`). Archives list entries in arbitrary order and often omit
+/// intermediate directory entries, so missing parent directories are inferred from each
+/// entry's path components.
+///
+/// Each file entry's `is_too_large` is derived from its declared `size`, same as
+/// `build_file_node` does for real files — but unlike a real file's size, an archive
+/// member's declared size is metadata the archive itself controls, so a crafted entry
+/// could under-report it and have `is_too_large: false` here even though it expands to
+/// far more data. That's cosmetic, not a safety hole: `read_archive_entry`'s read cap
+/// (see its doc comment) bounds the actual bytes read regardless of what this flag says.
+pub fn synthesize_archive_tree(
+    archive_path: &Path,
+    kind: ArchiveKind,
+    archive_node_name: &str,
+) -> Result<TreeNode, String> {
+    let raw_entries = list_raw_entries(archive_path, kind)?;
+
+    let mut dir_children: std::collections::HashMap<String, Vec<TreeNode>> =
+        std::collections::HashMap::new();
+    let mut known_dirs: std::collections::HashSet<String> = std::collections::HashSet::new();
+    known_dirs.insert(String::new());
+    dir_children.insert(String::new(), Vec::new());
+
+    fn ensure_dir_chain(
+        dir_path: &str,
+        dir_children: &mut std::collections::HashMap<String, Vec<TreeNode>>,
+        known_dirs: &mut std::collections::HashSet<String>,
+    ) {
+        if dir_path.is_empty() || known_dirs.contains(dir_path) {
+            return;
+        }
+
+        let (parent, name) = match dir_path.rfind('/') {
+            Some(i) => (&dir_path[..i], &dir_path[i + 1..]),
+            None => ("", dir_path),
+        };
+        ensure_dir_chain(parent, dir_children, known_dirs);
+
+        known_dirs.insert(dir_path.to_string());
+        dir_children.insert(dir_path.to_string(), Vec::new());
+        dir_children
+            .get_mut(parent)
+            .expect("parent directory was just ensured")
+            .push(TreeNode {
+                name: name.to_string(),
+                path: dir_path.to_string(),
+                node_type: "dir".to_string(),
+                children: Some(Vec::new()),
+                size_bytes: None,
+                is_too_large: false,
+                aggregated_size: 0,
+                percent_of_parent: 0.0,
+            });
+    }
+
+    for entry in &raw_entries {
+        let trimmed = entry.path.trim_end_matches('/');
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let (parent, name) = match trimmed.rfind('/') {
+            Some(i) => (&trimmed[..i], &trimmed[i + 1..]),
+            None => ("", trimmed),
+        };
+        ensure_dir_chain(parent, &mut dir_children, &mut known_dirs);
+
+        if entry.is_dir {
+            ensure_dir_chain(trimmed, &mut dir_children, &mut known_dirs);
+        } else {
+            dir_children
+                .entry(parent.to_string())
+                .or_default()
+                .push(TreeNode {
+                    name: name.to_string(),
+                    path: trimmed.to_string(),
+                    node_type: "file".to_string(),
+                    children: None,
+                    size_bytes: Some(entry.size),
+                    is_too_large: entry.size > LARGE_FILE_THRESHOLD,
+                    aggregated_size: entry.size,
+                    percent_of_parent: 0.0,
+                });
+        }
+    }
+
+    fn build(dir_path: &str, dir_children: &std::collections::HashMap<String, Vec<TreeNode>>) -> Vec<TreeNode> {
+        let mut children = dir_children.get(dir_path).cloned().unwrap_or_default();
+        for child in &mut children {
+            if child.node_type == "dir" {
+                child.children = Some(build(&child.path, dir_children));
+            }
+        }
+        children
+    }
+
+    Ok(TreeNode {
+        name: archive_node_name.to_string(),
+        path: archive_node_name.to_string(),
+        node_type: "archive".to_string(),
+        children: Some(build("", &dir_children)),
+        size_bytes: None,
+        is_too_large: false,
+        aggregated_size: 0,
+        percent_of_parent: 0.0,
+    })
+}
+
+/// Splits a virtual path like `logs.tar.gz/inner/dir/file.txt` into the real on-disk
+/// archive file and the member path inside it, by walking `rel_path`'s components and
+/// checking each prefix against the filesystem. Returns `None` if no prefix of
+/// `rel_path` names a real archive file (i.e. the path isn't inside an archive at all).
+pub fn split_archive_path(root: &Path, rel_path: &str) -> Option<(PathBuf, ArchiveKind, String)> {
+    let parts: Vec<&str> = rel_path.split('/').filter(|p| !p.is_empty()).collect();
+    let mut prefix = PathBuf::new();
+
+    for (i, part) in parts.iter().enumerate() {
+        prefix.push(part);
+        let candidate = root.join(&prefix);
+        if candidate.is_file() {
+            let kind = ArchiveKind::for_name(part)?;
+            if i + 1 >= parts.len() {
+                return None;
+            }
+            let inner = parts[i + 1..].join("/");
+            return Some((candidate, kind, inner));
+        }
+    }
+
+    None
+}
+
+/// Reads at most `max_bytes` of one member's bytes out of an archive without extracting
+/// the rest of it, returning the bytes actually read alongside the member's declared total
+/// size. A member's header can claim any size it likes (or, for zip, keep decompressing
+/// past what it claims), so bounding the physical read to `max_bytes` is what actually
+/// caps memory use — callers that only need a byte window should pass the window's end
+/// (`read_text_file_range` passes `offset + length + 1`, one probe byte past it) rather
+/// than the member's full declared size, the same way a crafted path is rejected by
+/// `reject_unsafe_member` regardless of what the archive claims about itself.
+pub fn read_archive_entry(
+    archive_path: &Path,
+    kind: ArchiveKind,
+    member_path: &str,
+    max_bytes: u64,
+) -> Result<(Vec<u8>, u64), String> {
+    reject_unsafe_member(member_path)?;
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+
+    match kind {
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader: Box<dyn Read> = if kind == ArchiveKind::TarGz {
+                Box::new(flate2::read::GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive
+                .entries()
+                .map_err(|e| format!("Failed to read archive entries: {}", e))?
+            {
+                let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+                let path = entry
+                    .path()
+                    .map_err(|e| format!("Invalid archive entry path: {}", e))?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                if path.trim_end_matches('/') == member_path {
+                    let declared_size = entry.header().size().unwrap_or(0);
+                    let mut bytes = Vec::new();
+                    entry
+                        .by_ref()
+                        .take(max_bytes)
+                        .read_to_end(&mut bytes)
+                        .map_err(|e| format!("Failed to read archive member: {}", e))?;
+                    return Ok((bytes, declared_size));
+                }
+            }
+            Err(format!("Archive member not found: {}", member_path))
+        }
+        ArchiveKind::Zip => {
+            let mut archive =
+                zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip: {}", e))?;
+            let mut entry = archive
+                .by_name(member_path)
+                .map_err(|_| format!("Archive member not found: {}", member_path))?;
+            let declared_size = entry.size();
+            let mut bytes = Vec::new();
+            entry
+                .by_ref()
+                .take(max_bytes)
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read archive member: {}", e))?;
+            Ok((bytes, declared_size))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_kind_for_name() {
+        assert_eq!(ArchiveKind::for_name("logs.tar.gz"), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::for_name("logs.tgz"), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::for_name("logs.tar"), Some(ArchiveKind::Tar));
+        assert_eq!(ArchiveKind::for_name("logs.zip"), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::for_name("logs.txt"), None);
+    }
+
+    #[test]
+    fn test_reject_unsafe_member() {
+        assert!(reject_unsafe_member("inner/file.txt").is_ok());
+        assert!(reject_unsafe_member("/etc/passwd").is_err());
+        assert!(reject_unsafe_member("../escape.txt").is_err());
+    }
+
+    fn write_sample_tar(path: &Path, member_name: &str, content: &[u8]) {
+        let file = File::create(path).unwrap();
+        let mut builder = tar::Builder::new(file);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, member_name, content).unwrap();
+        builder.finish().unwrap();
+    }
+
+    /// Regression test for a crafted archive forcing an unbounded read: even though
+    /// `big.txt` is far larger than the requested window, `read_archive_entry` must never
+    /// buffer more than `max_bytes`, while still reporting the member's true declared size
+    /// so callers can tell the result was truncated.
+    #[test]
+    fn test_read_archive_entry_bounds_read_to_max_bytes() {
+        let dir = std::env::temp_dir().join(format!(
+            "ponder_archive_test_{}_{}",
+            std::process::id(),
+            "cap_read"
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive_path = dir.join("sample.tar");
+        let content = vec![b'a'; 10_000];
+        write_sample_tar(&archive_path, "big.txt", &content);
+
+        let (bytes, declared_size) =
+            read_archive_entry(&archive_path, ArchiveKind::Tar, "big.txt", 100).unwrap();
+        assert_eq!(bytes.len(), 100);
+        assert_eq!(declared_size, 10_000);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Regression test for `is_too_large` always being reported as `false` for archive
+    /// members: an entry whose declared size exceeds `LARGE_FILE_THRESHOLD` must be flagged
+    /// the same way `build_file_node` flags an oversized real file.
+    #[test]
+    fn test_synthesize_archive_tree_flags_oversized_entries() {
+        let dir = std::env::temp_dir().join(format!(
+            "ponder_archive_test_{}_{}",
+            std::process::id(),
+            "flag_oversized"
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive_path = dir.join("sample.tar");
+        let big_content = vec![b'a'; (LARGE_FILE_THRESHOLD + 1) as usize];
+        write_sample_tar(&archive_path, "huge.bin", &big_content);
+
+        let tree = synthesize_archive_tree(&archive_path, ArchiveKind::Tar, "sample.tar").unwrap();
+        let huge = tree
+            .children
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == "huge.bin")
+            .expect("entry should be present");
+        assert!(huge.is_too_large);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
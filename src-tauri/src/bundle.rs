@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::commands::{
+    is_binary_bytes, path_should_be_ignored, to_relative_posix_path, LARGE_FILE_THRESHOLD, MAX_DEPTH,
+    MAX_NODES,
+};
+use crate::ignore::IgnoreEngine;
+
+const BUNDLE_FORMAT_VERSION: u8 = 1;
+
+/// One node of a bundle's manifest tree. Directories carry `children`; files carry the
+/// `(offset, length)` of their bytes within the bundle's data section.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct BundleEntry {
+    name: String,
+    path: String,
+    node_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<BundleEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    length: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct BundleManifest {
+    version: u8,
+    root: BundleEntry,
+}
+
+/// Packs every readable text file under `root` into one portable bundle file: an 8-byte
+/// little-endian manifest length, the JSON-serialized [`BundleManifest`], then the
+/// concatenated bytes of every included file (the "data section"). Reuses the same
+/// ignore rules, binary-file check, and large-file threshold as `list_tree`/
+/// `read_text_file` to decide what's worth including.
+pub fn bundle_workspace(root: &Path, max_bytes: u64) -> Result<PathBuf, String> {
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to canonicalize root: {}", e))?;
+
+    let ignore_engine = IgnoreEngine::new(&canonical_root);
+
+    let mut blob: Vec<u8> = Vec::new();
+    let mut dir_children: HashMap<PathBuf, Vec<BundleEntry>> = HashMap::new();
+    dir_children.insert(canonical_root.clone(), Vec::new());
+
+    let mut entries: Vec<walkdir::DirEntry> = WalkDir::new(&canonical_root)
+        .max_depth(MAX_DEPTH)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            !path_should_be_ignored(
+                e.path(),
+                e.file_type().is_dir(),
+                e.file_type().is_file(),
+                e.file_type().is_symlink(),
+                &canonical_root,
+                Some(&ignore_engine),
+            )
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path() != canonical_root)
+        .take(MAX_NODES)
+        .collect();
+
+    entries.sort_by_key(|e| e.depth());
+
+    for entry in &entries {
+        if entry.file_type().is_dir() {
+            dir_children.insert(entry.path().to_path_buf(), Vec::new());
+        }
+    }
+
+    for entry in entries {
+        let entry_path = entry.path();
+        let parent_path = entry_path.parent().unwrap_or(&canonical_root);
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rel_path = to_relative_posix_path(entry_path, &canonical_root);
+
+        let node = if entry.file_type().is_dir() {
+            BundleEntry {
+                name,
+                path: rel_path,
+                node_type: "dir".to_string(),
+                children: Some(Vec::new()),
+                offset: None,
+                length: None,
+            }
+        } else {
+            match read_bundlable_file(entry_path, max_bytes) {
+                Some(bytes) => {
+                    let offset = blob.len() as u64;
+                    let length = bytes.len() as u64;
+                    blob.extend(bytes);
+                    BundleEntry {
+                        name,
+                        path: rel_path,
+                        node_type: "file".to_string(),
+                        children: None,
+                        offset: Some(offset),
+                        length: Some(length),
+                    }
+                }
+                None => continue,
+            }
+        };
+
+        if let Some(children) = dir_children.get_mut(parent_path) {
+            children.push(node);
+        }
+    }
+
+    let root_entry = assemble_tree(&canonical_root, &canonical_root, &dir_children);
+
+    let manifest = BundleManifest {
+        version: BUNDLE_FORMAT_VERSION,
+        root: root_entry,
+    };
+
+    let manifest_bytes =
+        serde_json::to_vec(&manifest).map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?;
+
+    let root_name = canonical_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workspace".to_string());
+    let output_path = canonical_root
+        .parent()
+        .unwrap_or(&canonical_root)
+        .join(format!("{}.ponderbundle", root_name));
+
+    let mut file =
+        fs::File::create(&output_path).map_err(|e| format!("Failed to create bundle file: {}", e))?;
+    file.write_all(&(manifest_bytes.len() as u64).to_le_bytes())
+        .map_err(|e| format!("Failed to write bundle header: {}", e))?;
+    file.write_all(&manifest_bytes)
+        .map_err(|e| format!("Failed to write bundle manifest: {}", e))?;
+    file.write_all(&blob)
+        .map_err(|e| format!("Failed to write bundle data: {}", e))?;
+
+    Ok(output_path)
+}
+
+/// Recursively assembles the nested manifest tree for `dir` out of the flat
+/// `dir_children` map (keyed by absolute directory path), mirroring how
+/// `build_tree`'s `populate_children` turns its flat `dir_map` into a hierarchy.
+fn assemble_tree(
+    dir: &Path,
+    root: &Path,
+    dir_children: &HashMap<PathBuf, Vec<BundleEntry>>,
+) -> BundleEntry {
+    let name = if dir == root {
+        dir.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| dir.to_string_lossy().to_string())
+    } else {
+        dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    };
+    let path = to_relative_posix_path(dir, root);
+
+    let mut children = dir_children.get(dir).cloned().unwrap_or_default();
+    for child in &mut children {
+        if child.node_type == "dir" {
+            let child_dir = if child.path.is_empty() {
+                root.to_path_buf()
+            } else {
+                root.join(child.path.replace('/', std::path::MAIN_SEPARATOR_STR))
+            };
+            let rebuilt = assemble_tree(&child_dir, root, dir_children);
+            child.children = rebuilt.children;
+        }
+    }
+
+    BundleEntry {
+        name,
+        path,
+        node_type: "dir".to_string(),
+        children: Some(children),
+        offset: None,
+        length: None,
+    }
+}
+
+fn read_bundlable_file(path: &Path, max_bytes: u64) -> Option<Vec<u8>> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > LARGE_FILE_THRESHOLD || metadata.len() > max_bytes {
+        return None;
+    }
+
+    let bytes = fs::read(path).ok()?;
+    if is_binary_bytes(&bytes[..bytes.len().min(8192)]) {
+        return None;
+    }
+
+    Some(bytes)
+}
+
+/// Parses a bundle's manifest once and slices out `rel_path`'s bytes directly, without
+/// extracting the rest of the bundle.
+pub fn read_from_bundle(bundle_path: &Path, rel_path: &str) -> Result<String, String> {
+    let mut file =
+        fs::File::open(bundle_path).map_err(|e| format!("Failed to open bundle: {}", e))?;
+    let file_len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read bundle metadata: {}", e))?
+        .len();
+
+    let mut len_bytes = [0u8; 8];
+    file.read_exact(&mut len_bytes)
+        .map_err(|e| format!("Failed to read bundle header: {}", e))?;
+    let manifest_len = u64::from_le_bytes(len_bytes);
+
+    if manifest_len > file_len.saturating_sub(8) {
+        return Err("Not a valid bundle: manifest length exceeds file size".to_string());
+    }
+
+    let mut manifest_bytes = vec![0u8; manifest_len as usize];
+    file.read_exact(&mut manifest_bytes)
+        .map_err(|e| format!("Failed to read bundle manifest: {}", e))?;
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| format!("Failed to parse bundle manifest: {}", e))?;
+
+    let entry = find_entry(&manifest.root, rel_path)
+        .ok_or_else(|| format!("File not found in bundle: {}", rel_path))?;
+
+    let (offset, length) = match (entry.offset, entry.length) {
+        (Some(offset), Some(length)) => (offset, length),
+        _ => return Err(format!("Not a file in bundle: {}", rel_path)),
+    };
+
+    let data_section_start = 8 + manifest_len;
+    let data_section_len = file_len.saturating_sub(data_section_start);
+    if offset.checked_add(length).map(|end| end > data_section_len).unwrap_or(true) {
+        return Err("Not a valid bundle: entry bounds exceed file size".to_string());
+    }
+
+    file.seek(SeekFrom::Start(data_section_start + offset))
+        .map_err(|e| format!("Failed to seek into bundle: {}", e))?;
+
+    let mut buffer = vec![0u8; length as usize];
+    file.read_exact(&mut buffer)
+        .map_err(|e| format!("Failed to read bundle entry: {}", e))?;
+
+    String::from_utf8(buffer).map_err(|e| format!("Failed to decode bundle entry: {}", e))
+}
+
+fn find_entry<'a>(node: &'a BundleEntry, rel_path: &str) -> Option<&'a BundleEntry> {
+    if node.path == rel_path && node.node_type == "file" {
+        return Some(node);
+    }
+    node.children
+        .as_ref()?
+        .iter()
+        .find_map(|child| find_entry(child, rel_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_entry_locates_nested_file() {
+        let root = BundleEntry {
+            name: "root".to_string(),
+            path: "".to_string(),
+            node_type: "dir".to_string(),
+            children: Some(vec![BundleEntry {
+                name: "src".to_string(),
+                path: "src".to_string(),
+                node_type: "dir".to_string(),
+                children: Some(vec![BundleEntry {
+                    name: "main.rs".to_string(),
+                    path: "src/main.rs".to_string(),
+                    node_type: "file".to_string(),
+                    children: None,
+                    offset: Some(0),
+                    length: Some(10),
+                }]),
+                offset: None,
+                length: None,
+            }]),
+            offset: None,
+            length: None,
+        };
+
+        let found = find_entry(&root, "src/main.rs").expect("entry should be found");
+        assert_eq!(found.length, Some(10));
+        assert!(find_entry(&root, "src/missing.rs").is_none());
+    }
+
+    /// A truncated or non-bundle file must fail cleanly, not attempt to allocate a buffer
+    /// sized off of an attacker/garbage-controlled header value.
+    #[test]
+    fn test_read_from_bundle_rejects_header_larger_than_file() {
+        let path = std::env::temp_dir().join(format!(
+            "ponder_bundle_test_{}_{}",
+            std::process::id(),
+            "corrupt_header"
+        ));
+        fs::write(&path, (u64::MAX >> 1).to_le_bytes()).unwrap();
+
+        let result = read_from_bundle(&path, "src/main.rs");
+        assert!(result.is_err());
+
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::commands::{
+    aggregate_existing_children, build_file_node, collapse_below_threshold,
+    path_should_be_ignored, to_relative_posix_path, TreeNode,
+};
+use crate::ignore::IgnoreEngine;
+
+const CACHE_STORE_FILE: &str = "tree-cache.json";
+const CACHE_FORMAT_VERSION: u8 = 1;
+const MAX_DEPTH: usize = 10;
+const MAX_NODES: usize = 50_000;
+
+/// One immediate child's name, kind and `(mtime, size)` pair, as recorded in its parent's
+/// [`CachedDir`]; a file whose signature matches the previous scan's has its cached node
+/// reused instead of being rebuilt.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct ChildSignature {
+    name: String,
+    is_dir: bool,
+    mtime: u64,
+    size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedDir {
+    mtime: u64,
+    children: Vec<ChildSignature>,
+    node: TreeNode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CacheIndex {
+    version: u8,
+    dirs: HashMap<String, CachedDir>,
+}
+
+impl Default for CacheIndex {
+    fn default() -> Self {
+        CacheIndex {
+            version: CACHE_FORMAT_VERSION,
+            dirs: HashMap::new(),
+        }
+    }
+}
+
+/// Truncated to whole seconds so filesystems with coarse mtime resolution don't cause
+/// spurious cache misses.
+fn truncated_mtime(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn load_index(app: &AppHandle, canonical_root: &Path) -> CacheIndex {
+    let Ok(store) = app.store(CACHE_STORE_FILE) else {
+        return CacheIndex::default();
+    };
+
+    let key = canonical_root.to_string_lossy().to_string();
+    store
+        .get(&key)
+        .and_then(|value| serde_json::from_value::<CacheIndex>(value).ok())
+        .filter(|index| index.version == CACHE_FORMAT_VERSION)
+        .unwrap_or_default()
+}
+
+fn save_index(app: &AppHandle, canonical_root: &Path, index: &CacheIndex) {
+    let Ok(store) = app.store(CACHE_STORE_FILE) else {
+        return;
+    };
+
+    let key = canonical_root.to_string_lossy().to_string();
+    if let Ok(value) = serde_json::to_value(index) {
+        store.set(key, value);
+        let _ = store.save();
+    }
+}
+
+/// Reads one directory's immediate entries (not recursing), filtering out anything
+/// `path_should_be_ignored` would drop, and returns both the signatures used for
+/// cache-hit comparison and the filtered entries to recurse into on a miss.
+fn read_immediate_entries(
+    dir: &Path,
+    canonical_root: &Path,
+    ignore_engine: Option<&IgnoreEngine>,
+) -> (Vec<ChildSignature>, Vec<(PathBuf, String, bool)>) {
+    let mut signatures = Vec::new();
+    let mut fs_entries = Vec::new();
+
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return (signatures, fs_entries);
+    };
+
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let is_symlink = file_type.is_symlink();
+        let is_dir = if is_symlink { path.is_dir() } else { file_type.is_dir() };
+        let is_file = if is_symlink { path.is_file() } else { file_type.is_file() };
+
+        if path_should_be_ignored(&path, is_dir, is_file, is_symlink, canonical_root, ignore_engine) {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        let mtime = truncated_mtime(&path);
+        let size = if is_dir {
+            0
+        } else {
+            fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+        };
+
+        signatures.push(ChildSignature {
+            name: name.clone(),
+            is_dir,
+            mtime,
+            size,
+        });
+        fs_entries.push((path, name, is_dir));
+    }
+
+    signatures.sort_by(|a, b| a.name.cmp(&b.name));
+    fs_entries.sort_by(|a, b| a.1.cmp(&b.1));
+
+    (signatures, fs_entries)
+}
+
+/// Recursively (re)validates `dir` and every directory beneath it against `prev_index`,
+/// so a change to a deeply-nested file is always detected: a directory's own mtime only
+/// moves when an entry is added or removed directly within it, so comparing just `dir`'s
+/// signature and stopping there would miss edits further down (the ancestor's mtime never
+/// changes). On a cache hit only the per-file rebuild below is skipped — re-reading
+/// a file's bytes (in particular, re-parsing it as an archive) is the expensive part this
+/// cache exists to avoid, not the directory listing itself.
+#[allow(clippy::too_many_arguments)]
+fn build_dir_cached(
+    dir: &Path,
+    root: &Path,
+    canonical_root: &Path,
+    ignore_engine: Option<&IgnoreEngine>,
+    depth: usize,
+    node_count: &mut usize,
+    prev_index: &CacheIndex,
+    next_index: &mut CacheIndex,
+) -> TreeNode {
+    let rel = to_relative_posix_path(dir, root);
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.to_string_lossy().to_string());
+
+    let dir_mtime = truncated_mtime(dir);
+    let (signatures, fs_entries) = read_immediate_entries(dir, canonical_root, ignore_engine);
+
+    let prev_cached = prev_index.dirs.get(&rel);
+    let prev_children_by_name: HashMap<&str, &TreeNode> = prev_cached
+        .and_then(|cached| cached.node.children.as_ref())
+        .map(|children| children.iter().map(|c| (c.name.as_str(), c)).collect())
+        .unwrap_or_default();
+    let prev_signature_by_name: HashMap<&str, &ChildSignature> = prev_cached
+        .map(|cached| cached.children.iter().map(|s| (s.name.as_str(), s)).collect())
+        .unwrap_or_default();
+
+    let mut children = Vec::new();
+    if depth < MAX_DEPTH {
+        for (path, entry_name, is_dir) in fs_entries {
+            if *node_count >= MAX_NODES {
+                break;
+            }
+            *node_count += 1;
+
+            if is_dir {
+                children.push(build_dir_cached(
+                    &path,
+                    root,
+                    canonical_root,
+                    ignore_engine,
+                    depth + 1,
+                    node_count,
+                    prev_index,
+                    next_index,
+                ));
+                continue;
+            }
+
+            let current_signature = signatures.iter().find(|s| s.name == entry_name);
+            let unchanged = current_signature
+                .zip(prev_signature_by_name.get(entry_name.as_str()))
+                .is_some_and(|(current, prev)| current == *prev);
+
+            if unchanged {
+                if let Some(prev_node) = prev_children_by_name.get(entry_name.as_str()) {
+                    children.push((*prev_node).clone());
+                    continue;
+                }
+            }
+
+            let child_rel = to_relative_posix_path(&path, root);
+            children.push(build_file_node(&path, entry_name, child_rel));
+        }
+    }
+
+    let node = TreeNode {
+        name,
+        path: rel.clone(),
+        node_type: "dir".to_string(),
+        children: Some(children),
+        size_bytes: None,
+        is_too_large: false,
+        aggregated_size: 0,
+        percent_of_parent: if rel.is_empty() { 100.0 } else { 0.0 },
+    };
+
+    next_index.dirs.insert(
+        rel,
+        CachedDir {
+            mtime: dir_mtime,
+            children: signatures,
+            node: node.clone(),
+        },
+    );
+
+    node
+}
+
+/// Cached counterpart to `crate::commands::build_tree`.
+///
+/// This does **not** skip descending into directories based on their own mtime: a
+/// directory's mtime only moves when an entry is added, removed, or renamed directly
+/// within it, never when a file further down is merely edited, so a cache that trusted a
+/// directory's mtime to mean "nothing below changed" would silently miss nested edits
+/// (see `test_build_dir_cached_picks_up_nested_file_mutation`, and `bca109c`, the commit
+/// that removed that exact shortcut after it shipped). Short of watching the filesystem
+/// for changes (e.g. via `inotify`), detecting a nested edit requires re-stating every
+/// directory on the path down to it, so `build_dir_cached` always performs the full
+/// `read_dir`/mtime pass instead.
+///
+/// What *is* cached, and what actually dominates repeat-scan cost in practice, is the
+/// per-file work: reusing each unchanged file's previously-built node means a large
+/// workspace's files don't get re-read, and in particular an unchanged archive doesn't get
+/// re-opened and re-parsed into its virtual subtree on every scan.
+pub fn list_tree_cached(
+    app: &AppHandle,
+    canonical_root: &Path,
+    ignore_engine: Option<&IgnoreEngine>,
+    sort_by_size: bool,
+    others_threshold_percent: Option<f32>,
+) -> Result<TreeNode, String> {
+    let prev_index = load_index(app, canonical_root);
+    let mut next_index = CacheIndex::default();
+    let mut node_count = 0usize;
+
+    let mut root_node = build_dir_cached(
+        canonical_root,
+        canonical_root,
+        canonical_root,
+        ignore_engine,
+        0,
+        &mut node_count,
+        &prev_index,
+        &mut next_index,
+    );
+
+    save_index(app, canonical_root, &next_index);
+
+    root_node.aggregated_size = aggregate_existing_children(&mut root_node, sort_by_size);
+
+    if let Some(threshold_percent) = others_threshold_percent {
+        collapse_below_threshold(&mut root_node, threshold_percent);
+    }
+
+    Ok(root_node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_signature_equality_detects_changes() {
+        let a = ChildSignature {
+            name: "file.txt".to_string(),
+            is_dir: false,
+            mtime: 100,
+            size: 10,
+        };
+        let b = ChildSignature {
+            mtime: 200,
+            ..a.clone()
+        };
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+    }
+
+    /// Regression test for a nested-file edit not invalidating the top-level cache entry:
+    /// `a`'s own mtime never changes when only `a/b/f.txt`'s content does, so the cached
+    /// tree must still pick up the change on the next `build_dir_cached` pass.
+    #[test]
+    fn test_build_dir_cached_picks_up_nested_file_mutation() {
+        let root = std::env::temp_dir().join(format!(
+            "ponder_cache_test_{}_{}",
+            std::process::id(),
+            "nested_mutation"
+        ));
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/b/f.txt"), "hello").unwrap();
+
+        let mut node_count = 0usize;
+        let mut first_index = CacheIndex::default();
+        build_dir_cached(
+            &root,
+            &root,
+            &root,
+            None,
+            0,
+            &mut node_count,
+            &CacheIndex::default(),
+            &mut first_index,
+        );
+
+        fs::write(root.join("a/b/f.txt"), "hello, world! this content is much longer now").unwrap();
+
+        let mut node_count = 0usize;
+        let mut second_index = CacheIndex::default();
+        let second_tree = build_dir_cached(
+            &root,
+            &root,
+            &root,
+            None,
+            0,
+            &mut node_count,
+            &first_index,
+            &mut second_index,
+        );
+
+        let file_node = find_node(&second_tree, "a/b/f.txt").expect("nested file should be present");
+        assert_eq!(
+            file_node.size_bytes,
+            Some(fs::metadata(root.join("a/b/f.txt")).unwrap().len())
+        );
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    fn find_node<'a>(node: &'a TreeNode, path: &str) -> Option<&'a TreeNode> {
+        if node.path == path {
+            return Some(node);
+        }
+        node.children.as_ref()?.iter().find_map(|c| find_node(c, path))
+    }
+}
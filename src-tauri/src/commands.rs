@@ -1,11 +1,13 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-const MAX_DEPTH: usize = 10;
-const MAX_NODES: usize = 50_000;
-const LARGE_FILE_THRESHOLD: u64 = 2 * 1024 * 1024; // 2 MB
+use crate::ignore::IgnoreEngine;
+
+pub(crate) const MAX_DEPTH: usize = 10;
+pub(crate) const MAX_NODES: usize = 50_000;
+pub(crate) const LARGE_FILE_THRESHOLD: u64 = 2 * 1024 * 1024; // 2 MB
 const DEFAULT_MAX_READ_BYTES: u64 = 200 * 1024; // 200 KB
 const ALWAYS_IGNORED_DIRS: &[&str] = &[
     ".git",
@@ -20,17 +22,19 @@ const ALWAYS_IGNORED_DIRS: &[&str] = &[
 
 const ALLOWED_HIDDEN_DIRS: &[&str] = &[".github", ".vscode"];
 const ALWAYS_IGNORED_FILES: &[&str] = &[".DS_Store"];
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TreeNode {
     pub name: String,
     pub path: String,
     pub node_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<TreeNode>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub size_bytes: Option<u64>,
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub is_too_large: bool,
+    pub aggregated_size: u64,
+    pub percent_of_parent: f32,
 }
 #[derive(Serialize)]
 pub struct FileReadResult {
@@ -54,23 +58,50 @@ fn validate_path_within_root(path: &Path, root: &Path) -> Result<PathBuf, String
     }
 }
 
-fn to_relative_posix_path(path: &Path, root: &Path) -> String {
+pub(crate) fn to_relative_posix_path(path: &Path, root: &Path) -> String {
     path.strip_prefix(root)
         .unwrap_or(path)
         .to_string_lossy()
         .replace('\\', "/")
 }
 
-fn should_ignore_entry(entry: &walkdir::DirEntry, root: &Path) -> bool {
-    let name = entry.file_name().to_string_lossy();
+fn should_ignore_entry(
+    entry: &walkdir::DirEntry,
+    root: &Path,
+    ignore_engine: Option<&IgnoreEngine>,
+) -> bool {
+    path_should_be_ignored(
+        entry.path(),
+        entry.file_type().is_dir(),
+        entry.file_type().is_file(),
+        entry.file_type().is_symlink(),
+        root,
+        ignore_engine,
+    )
+}
+
+/// Shared ignore-rule evaluation used both by the `WalkDir`-driven full scan and by
+/// [`crate::cache`]'s directory-at-a-time cached scan.
+pub(crate) fn path_should_be_ignored(
+    path: &Path,
+    is_dir: bool,
+    is_file: bool,
+    is_symlink: bool,
+    root: &Path,
+    ignore_engine: Option<&IgnoreEngine>,
+) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
 
-    if entry.file_type().is_symlink() {
-        if entry.file_type().is_file() {
-            if let Ok(target) = fs::read_link(entry.path()) {
+    if is_symlink {
+        if is_file {
+            if let Ok(target) = fs::read_link(path) {
                 let resolved = if target.is_absolute() {
                     target
                 } else {
-                    entry.path().parent().unwrap_or(entry.path()).join(&target)
+                    path.parent().unwrap_or(path).join(&target)
                 };
                 if let Ok(canonical_target) = resolved.canonicalize() {
                     if let Ok(canonical_root) = root.canonicalize() {
@@ -80,40 +111,177 @@ fn should_ignore_entry(entry: &walkdir::DirEntry, root: &Path) -> bool {
             }
             return true;
         }
-        return entry.file_type().is_dir();
+        return is_dir;
     }
 
-    if entry.file_type().is_dir() && ALWAYS_IGNORED_DIRS.contains(&name.as_ref()) {
+    if is_dir && ALWAYS_IGNORED_DIRS.contains(&name.as_ref()) {
         return true;
     }
 
-    if entry.file_type().is_file() && ALWAYS_IGNORED_FILES.contains(&name.as_ref()) {
+    if is_file && ALWAYS_IGNORED_FILES.contains(&name.as_ref()) {
         return true;
     }
 
-    if entry.file_type().is_dir() && name.starts_with('.') {
+    if is_dir && name.starts_with('.') {
         return !ALLOWED_HIDDEN_DIRS.contains(&name.as_ref());
     }
 
+    if let Some(engine) = ignore_engine {
+        if engine.is_ignored(path, is_dir) {
+            return true;
+        }
+    }
+
     false
 }
 
-fn is_binary_file(path: &Path, check_bytes: usize) -> Result<bool, std::io::Error> {
-    let file = fs::File::open(path)?;
-    let mut reader = std::io::BufReader::new(file);
-    let mut buffer = vec![0u8; check_bytes];
+pub(crate) fn is_binary_bytes(bytes: &[u8]) -> bool {
+    bytes.contains(&0)
+}
+
+/// Builds the `TreeNode` for one file entry, transparently expanding it into an
+/// `archive`-typed node with a synthesized virtual subtree when its name matches a
+/// supported archive extension and it isn't already over the large-file threshold.
+pub(crate) fn build_file_node(entry_path: &Path, name: String, rel_path: String) -> TreeNode {
+    let metadata = fs::metadata(entry_path);
+    let (size_bytes, is_too_large) = match metadata {
+        Ok(m) => {
+            let size = m.len();
+            (Some(size), size > LARGE_FILE_THRESHOLD)
+        }
+        Err(_) => (None, false),
+    };
+
+    match crate::archive::ArchiveKind::for_name(&name) {
+        Some(kind) if !is_too_large => crate::archive::synthesize_archive_tree(entry_path, kind, &name)
+            .map(|mut archive_node| {
+                prefix_descendant_paths(&mut archive_node, &rel_path);
+                archive_node.path = rel_path.clone();
+                archive_node.size_bytes = size_bytes;
+                archive_node.is_too_large = is_too_large;
+                archive_node
+            })
+            .unwrap_or_else(|_| TreeNode {
+                name,
+                path: rel_path,
+                node_type: "file".to_string(),
+                children: None,
+                size_bytes,
+                is_too_large,
+                aggregated_size: 0,
+                percent_of_parent: 0.0,
+            }),
+        _ => TreeNode {
+            name,
+            path: rel_path,
+            node_type: "file".to_string(),
+            children: None,
+            size_bytes,
+            is_too_large,
+            aggregated_size: 0,
+            percent_of_parent: 0.0,
+        },
+    }
+}
+
+/// Rewrites an archive's synthesized virtual-tree paths (relative to the archive itself)
+/// into full workspace-relative paths, e.g. `inner/file.txt` under `logs.tar.gz` becomes
+/// `logs.tar.gz/inner/file.txt`.
+fn prefix_descendant_paths(node: &mut TreeNode, prefix: &str) {
+    if let Some(children) = &mut node.children {
+        for child in children {
+            child.path = format!("{}/{}", prefix, child.path);
+            prefix_descendant_paths(child, prefix);
+        }
+    }
+}
+
+/// Collapses directory entries below `threshold_percent` of their parent's
+/// aggregated size into a single synthetic `<others>` node, so a treemap view
+/// stays readable for directories with many small children.
+pub(crate) fn collapse_below_threshold(node: &mut TreeNode, threshold_percent: f32) {
+    let Some(children) = &mut node.children else {
+        return;
+    };
+
+    for child in children.iter_mut() {
+        collapse_below_threshold(child, threshold_percent);
+    }
+
+    let (keep, collapse): (Vec<TreeNode>, Vec<TreeNode>) = children
+        .drain(..)
+        .partition(|c| c.percent_of_parent >= threshold_percent);
+
+    *children = keep;
+
+    if !collapse.is_empty() {
+        let collapsed_size: u64 = collapse.iter().map(|c| c.aggregated_size).sum();
+        let percent_of_parent = if node.aggregated_size == 0 {
+            0.0
+        } else {
+            (collapsed_size as f32 / node.aggregated_size as f32) * 100.0
+        };
+
+        children.push(TreeNode {
+            name: "<others>".to_string(),
+            path: if node.path.is_empty() {
+                "<others>".to_string()
+            } else {
+                format!("{}/<others>", node.path)
+            },
+            node_type: "others".to_string(),
+            children: None,
+            size_bytes: None,
+            is_too_large: false,
+            aggregated_size: collapsed_size,
+            percent_of_parent,
+        });
+    }
+}
+
+/// Mirrors `populate_children`'s bottom-up aggregation, but for a subtree that is
+/// already fully built in memory (e.g. a synthesized archive tree) rather than one
+/// driven by `dir_map`.
+pub(crate) fn aggregate_existing_children(node: &mut TreeNode, sort_by_size: bool) -> u64 {
+    let Some(children) = &mut node.children else {
+        return node.size_bytes.unwrap_or(0);
+    };
+
+    for child in children.iter_mut() {
+        child.aggregated_size = aggregate_existing_children(child, sort_by_size);
+    }
+
+    if sort_by_size {
+        children.sort_by(|a, b| b.aggregated_size.cmp(&a.aggregated_size));
+    } else {
+        children.sort_by(|a, b| match (&a.node_type[..], &b.node_type[..]) {
+            ("dir", "file") => std::cmp::Ordering::Less,
+            ("file", "dir") => std::cmp::Ordering::Greater,
+            ("archive", "file") => std::cmp::Ordering::Less,
+            ("file", "archive") => std::cmp::Ordering::Greater,
+            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        });
+    }
 
-    use std::io::Read;
-    let bytes_read = reader.read(&mut buffer)?;
-    buffer.truncate(bytes_read);
+    let total: u64 = children.iter().map(|c| c.aggregated_size).sum();
+    for child in children.iter_mut() {
+        child.percent_of_parent = if total == 0 {
+            0.0
+        } else {
+            (child.aggregated_size as f32 / total as f32) * 100.0
+        };
+    }
 
-    Ok(buffer.contains(&0))
+    total
 }
 
 fn build_tree(
     root: &Path,
     canonical_root: &Path,
     node_count: &mut usize,
+    ignore_engine: Option<&IgnoreEngine>,
+    sort_by_size: bool,
+    others_threshold_percent: Option<f32>,
 ) -> Result<TreeNode, String> {
     let root_name = root
         .file_name()
@@ -126,7 +294,7 @@ fn build_tree(
         .max_depth(MAX_DEPTH)
         .follow_links(false)
         .into_iter()
-        .filter_entry(|e| !should_ignore_entry(e, canonical_root))
+        .filter_entry(|e| !should_ignore_entry(e, canonical_root, ignore_engine))
     {
         if *node_count >= MAX_NODES {
             break;
@@ -151,6 +319,8 @@ fn build_tree(
         children: Some(Vec::new()),
         size_bytes: None,
         is_too_large: false,
+        aggregated_size: 0,
+        percent_of_parent: 100.0,
     };
 
     entries.sort_by_key(|e| e.depth());
@@ -174,25 +344,11 @@ fn build_tree(
                 children: Some(Vec::new()),
                 size_bytes: None,
                 is_too_large: false,
+                aggregated_size: 0,
+                percent_of_parent: 0.0,
             }
         } else {
-            let metadata = fs::metadata(entry_path);
-            let (size_bytes, is_too_large) = match metadata {
-                Ok(m) => {
-                    let size = m.len();
-                    (Some(size), size > LARGE_FILE_THRESHOLD)
-                }
-                Err(_) => (None, false),
-            };
-
-            TreeNode {
-                name,
-                path: rel_path,
-                node_type: "file".to_string(),
-                children: None,
-                size_bytes,
-                is_too_large,
-            }
+            build_file_node(entry_path, name, rel_path)
         };
 
         if let Some(children) = dir_map.get_mut(parent_path) {
@@ -205,40 +361,73 @@ fn build_tree(
         full_path: &Path,
         dir_map: &std::collections::HashMap<PathBuf, Vec<TreeNode>>,
         root: &Path,
-    ) {
+        sort_by_size: bool,
+    ) -> u64 {
         if let Some(children) = dir_map.get(full_path) {
-            let mut sorted_children: Vec<TreeNode> = children.clone();
+            let mut children: Vec<TreeNode> = children.clone();
 
-            sorted_children.sort_by(|a, b| {
-                match (&a.node_type[..], &b.node_type[..]) {
-                    ("dir", "file") => std::cmp::Ordering::Less,
-                    ("file", "dir") => std::cmp::Ordering::Greater,
-                    _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                }
-            });
-
-            for child in &mut sorted_children {
+            for child in &mut children {
                 if child.node_type == "dir" {
                     let child_path = if child.path.is_empty() {
                         root.to_path_buf()
                     } else {
                         root.join(&child.path.replace('/', std::path::MAIN_SEPARATOR_STR))
                     };
-                    populate_children(child, &child_path, dir_map, root);
+                    child.aggregated_size =
+                        populate_children(child, &child_path, dir_map, root, sort_by_size);
+                } else if child.node_type == "archive" {
+                    child.aggregated_size = aggregate_existing_children(child, sort_by_size);
+                } else {
+                    child.aggregated_size = child.size_bytes.unwrap_or(0);
                 }
             }
 
-            node.children = Some(sorted_children);
+            if sort_by_size {
+                children.sort_by(|a, b| b.aggregated_size.cmp(&a.aggregated_size));
+            } else {
+                children.sort_by(|a, b| match (&a.node_type[..], &b.node_type[..]) {
+                    ("dir", "file") => std::cmp::Ordering::Less,
+                    ("file", "dir") => std::cmp::Ordering::Greater,
+                    ("archive", "file") => std::cmp::Ordering::Less,
+                    ("file", "archive") => std::cmp::Ordering::Greater,
+                    _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                });
+            }
+
+            let aggregated_size: u64 = children.iter().map(|c| c.aggregated_size).sum();
+            for child in &mut children {
+                child.percent_of_parent = if aggregated_size == 0 {
+                    0.0
+                } else {
+                    (child.aggregated_size as f32 / aggregated_size as f32) * 100.0
+                };
+            }
+
+            node.children = Some(children);
+            aggregated_size
+        } else {
+            0
         }
     }
 
-    populate_children(&mut root_node, root, &dir_map, root);
+    root_node.aggregated_size = populate_children(&mut root_node, root, &dir_map, root, sort_by_size);
+
+    if let Some(threshold_percent) = others_threshold_percent {
+        collapse_below_threshold(&mut root_node, threshold_percent);
+    }
 
     Ok(root_node)
 }
 
 #[tauri::command]
-pub fn list_tree(root: String) -> Result<TreeNode, String> {
+pub fn list_tree(
+    app: tauri::AppHandle,
+    root: String,
+    use_ignore_file: Option<bool>,
+    sort_by_size: Option<bool>,
+    others_threshold_percent: Option<f32>,
+    use_cache: Option<bool>,
+) -> Result<TreeNode, String> {
     let root_path = Path::new(&root);
 
     if !root_path.exists() {
@@ -253,15 +442,84 @@ pub fn list_tree(root: String) -> Result<TreeNode, String> {
         .canonicalize()
         .map_err(|e| format!("Failed to canonicalize root: {}", e))?;
 
+    let ignore_engine = if use_ignore_file.unwrap_or(true) {
+        Some(IgnoreEngine::new(&canonical_root))
+    } else {
+        None
+    };
+
+    let sort_by_size = sort_by_size.unwrap_or(false);
+
+    if use_cache.unwrap_or(true) {
+        return crate::cache::list_tree_cached(
+            &app,
+            &canonical_root,
+            ignore_engine.as_ref(),
+            sort_by_size,
+            others_threshold_percent,
+        );
+    }
+
     let mut node_count = 0;
-    build_tree(&canonical_root, &canonical_root, &mut node_count)
+    build_tree(
+        &canonical_root,
+        &canonical_root,
+        &mut node_count,
+        ignore_engine.as_ref(),
+        sort_by_size,
+        others_threshold_percent,
+    )
 }
 
+/// Thin wrapper over [`read_text_file_range`] that reads from the start of the file, kept
+/// for callers that just want the whole (small) file as a string. Unlike
+/// `read_text_file_range`, which windows arbitrarily large files on purpose, this hard-fails
+/// when the file doesn't fit in one read instead of silently handing back a clipped prefix —
+/// a caller using this entry point has no way to tell `truncated` apart from a complete read,
+/// so returning a partial file as if it were the whole thing would be worse than erroring.
 #[tauri::command]
 pub fn read_text_file(root: String, rel_path: String, max_bytes: Option<u64>) -> Result<String, String> {
     let max_bytes = max_bytes.unwrap_or(DEFAULT_MAX_READ_BYTES);
+    let result = read_text_file_range(root, rel_path, 0, max_bytes)?;
+    if result.truncated {
+        return Err(format!(
+            "File too large: {} bytes exceeds the {} byte limit",
+            result.size_bytes, max_bytes
+        ));
+    }
+    Ok(result.content)
+}
+
+/// Reads a byte window `[offset, offset + length)` out of a file (or archive member)
+/// without loading the rest of it, so multi-megabyte logs can be paged through. Snaps
+/// the window to valid UTF-8 boundaries by trimming any partial multibyte sequence at
+/// its edges, so the returned `content` never fails to decode.
+#[tauri::command]
+pub fn read_text_file_range(
+    root: String,
+    rel_path: String,
+    offset: u64,
+    length: u64,
+) -> Result<FileReadResult, String> {
     let root_path = Path::new(&root);
 
+    if let Some((archive_path, kind, member_path)) =
+        crate::archive::split_archive_path(root_path, &rel_path)
+    {
+        validate_path_within_root(&archive_path, root_path)?;
+        let max_bytes = offset.saturating_add(length);
+        // Read one byte past the requested window: the only way to tell "the member ends
+        // exactly here" from "the member keeps going (or decompressing) past here" without
+        // trusting its (possibly forged) declared size.
+        let (bytes, declared_size) = crate::archive::read_archive_entry(
+            &archive_path,
+            kind,
+            &member_path,
+            max_bytes.saturating_add(1),
+        )?;
+        return read_window_from_archive_bytes(&bytes, declared_size, offset, length, max_bytes);
+    }
+
     let native_rel_path = rel_path.replace('/', std::path::MAIN_SEPARATOR_STR);
     let file_path = root_path.join(&native_rel_path);
 
@@ -273,23 +531,115 @@ pub fn read_text_file(root: String, rel_path: String, max_bytes: Option<u64>) ->
 
     let metadata = fs::metadata(&canonical_path)
         .map_err(|e| format!("Failed to read file metadata: {}", e))?;
+    let size_bytes = metadata.len();
 
-    if metadata.len() > max_bytes {
-        return Err(format!(
-            "File too large: {} bytes (max: {} bytes)",
-            metadata.len(),
-            max_bytes
-        ));
+    use std::io::{Read, Seek, SeekFrom};
+
+    let file = fs::File::open(&canonical_path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = std::io::BufReader::new(file);
+
+    if offset > 0 {
+        reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| format!("Failed to seek: {}", e))?;
     }
 
-    if is_binary_file(&canonical_path, 8192).unwrap_or(false) {
+    let window_len = length.min(size_bytes.saturating_sub(offset)) as usize;
+    let mut buffer = vec![0u8; window_len];
+    reader
+        .read_exact(&mut buffer)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    if offset == 0 && is_binary_bytes(&buffer[..buffer.len().min(8192)]) {
         return Err("Cannot display binary file".to_string());
     }
 
-    let content = fs::read_to_string(&canonical_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let truncated = offset + (window_len as u64) < size_bytes;
+    let content = trim_to_valid_utf8(buffer);
+
+    Ok(FileReadResult {
+        content,
+        size_bytes,
+        truncated,
+    })
+}
+
+/// Like the real-file windowed read above, but for archive members: `bytes` was read by the
+/// caller up to `max_bytes + 1` (see [`crate::archive::read_archive_entry`]), one byte past
+/// the requested window. That probe byte is what `truncated` is decided from, not
+/// `declared_size` — the member's size from the archive's own (possibly forged) metadata:
+/// no probe byte (`bytes.len() <= max_bytes`) means the read hit real EOF within the cap,
+/// which is definitive physical proof of the member's true length regardless of what
+/// `declared_size` claims either way; a probe byte present means there's more data past the
+/// window no matter what `declared_size` claims. Trusting `declared_size` over what was
+/// actually read — in either direction — would reopen the exact forged-size hole the read
+/// cap exists to close.
+fn read_window_from_archive_bytes(
+    bytes: &[u8],
+    declared_size: u64,
+    offset: u64,
+    length: u64,
+    max_bytes: u64,
+) -> Result<FileReadResult, String> {
+    let read_exceeded_window = bytes.len() as u64 > max_bytes;
+    let bytes = &bytes[..(max_bytes.min(bytes.len() as u64) as usize)];
+
+    let size_bytes = if read_exceeded_window {
+        declared_size.max(max_bytes + 1)
+    } else {
+        bytes.len() as u64
+    };
+    let window_start = offset.min(bytes.len() as u64) as usize;
+    let window_end = offset.saturating_add(length).min(bytes.len() as u64) as usize;
+    let window = &bytes[window_start..window_end];
+
+    if offset == 0 && is_binary_bytes(&window[..window.len().min(8192)]) {
+        return Err("Cannot display binary file".to_string());
+    }
+
+    let truncated = read_exceeded_window;
+    let content = trim_to_valid_utf8(window.to_vec());
+
+    Ok(FileReadResult {
+        content,
+        size_bytes,
+        truncated,
+    })
+}
+
+/// Trims any partial multibyte sequence at either edge of `bytes` so the remainder is
+/// valid UTF-8, since a byte-offset window can land in the middle of a character.
+fn trim_to_valid_utf8(mut bytes: Vec<u8>) -> String {
+    while !bytes.is_empty() {
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => break,
+            Err(e) => {
+                let valid_up_to = e.valid_up_to();
+                if valid_up_to == 0 {
+                    bytes.remove(0);
+                } else {
+                    bytes.truncate(valid_up_to);
+                }
+            }
+        }
+    }
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+/// Packs every readable text file under `root` into one portable `.ponderbundle` file
+/// next to the workspace, and returns the bundle's path.
+#[tauri::command]
+pub fn bundle_workspace(root: String, max_bytes: u64) -> Result<String, String> {
+    let root_path = Path::new(&root);
+    let bundle_path = crate::bundle::bundle_workspace(root_path, max_bytes)?;
+    Ok(bundle_path.to_string_lossy().to_string())
+}
 
-    Ok(content)
+/// Reads one file's contents out of a bundle produced by [`bundle_workspace`] without
+/// extracting the rest of it.
+#[tauri::command]
+pub fn read_from_bundle(bundle_path: String, rel_path: String) -> Result<String, String> {
+    crate::bundle::read_from_bundle(Path::new(&bundle_path), &rel_path)
 }
 
 #[cfg(test)]
@@ -320,4 +670,238 @@ mod tests {
         // Clean up
         fs::remove_dir_all(&root).ok();
     }
+
+    #[test]
+    fn test_trim_to_valid_utf8_drops_partial_multibyte_edges() {
+        // "héllo w" followed by just the leading byte of the 2-byte 'ö' sequence.
+        let mut cut = "héllo w".as_bytes().to_vec();
+        cut.push(0xC3);
+        let trimmed = trim_to_valid_utf8(cut);
+        assert_eq!(trimmed, "héllo w");
+    }
+
+    #[test]
+    fn test_read_text_file_range_reports_truncated_for_real_files() {
+        let root = env::temp_dir().join(format!(
+            "ponder_commands_test_{}_{}",
+            std::process::id(),
+            "read_text_file_range_truncation"
+        ));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("data.txt"), "0123456789").unwrap();
+        let root_str = root.to_string_lossy().to_string();
+
+        let result = read_text_file_range(root_str.clone(), "data.txt".to_string(), 0, 4).unwrap();
+        assert_eq!(result.content, "0123");
+        assert_eq!(result.size_bytes, 10);
+        assert!(result.truncated);
+
+        let result = read_text_file_range(root_str, "data.txt".to_string(), 0, 100).unwrap();
+        assert_eq!(result.content, "0123456789");
+        assert!(!result.truncated);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    fn leaf(name: &str, size: u64) -> TreeNode {
+        TreeNode {
+            name: name.to_string(),
+            path: name.to_string(),
+            node_type: "file".to_string(),
+            children: None,
+            size_bytes: Some(size),
+            is_too_large: false,
+            aggregated_size: 0,
+            percent_of_parent: 0.0,
+        }
+    }
+
+    fn dir(name: &str, children: Vec<TreeNode>) -> TreeNode {
+        TreeNode {
+            name: name.to_string(),
+            path: name.to_string(),
+            node_type: "dir".to_string(),
+            children: Some(children),
+            size_bytes: None,
+            is_too_large: false,
+            aggregated_size: 0,
+            percent_of_parent: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_existing_children_sums_multilevel_tree() {
+        let mut root = dir(
+            "root",
+            vec![
+                dir("sub", vec![leaf("a.txt", 10), leaf("b.txt", 20)]),
+                leaf("c.txt", 5),
+            ],
+        );
+
+        let total = aggregate_existing_children(&mut root, false);
+        assert_eq!(total, 35);
+
+        let children = root.children.as_ref().unwrap();
+        let sub = children.iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!(sub.aggregated_size, 30);
+        assert_eq!(sub.percent_of_parent, (30.0 / 35.0) * 100.0);
+
+        let c = children.iter().find(|c| c.name == "c.txt").unwrap();
+        assert_eq!(c.aggregated_size, 5);
+        assert_eq!(c.percent_of_parent, (5.0 / 35.0) * 100.0);
+    }
+
+    #[test]
+    fn test_aggregate_existing_children_sort_by_size_orders_descending() {
+        let mut root = dir(
+            "root",
+            vec![leaf("small.txt", 1), leaf("big.txt", 100), leaf("medium.txt", 10)],
+        );
+
+        aggregate_existing_children(&mut root, true);
+
+        let names: Vec<&str> = root
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["big.txt", "medium.txt", "small.txt"]);
+    }
+
+    #[test]
+    fn test_aggregate_existing_children_sorts_dirs_before_files_when_not_by_size() {
+        let mut root = dir("root", vec![leaf("z_file.txt", 1), dir("a_dir", vec![leaf("f.txt", 1)])]);
+
+        aggregate_existing_children(&mut root, false);
+
+        let names: Vec<&str> = root
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a_dir", "z_file.txt"]);
+    }
+
+    #[test]
+    fn test_collapse_below_threshold_folds_small_children_into_others() {
+        let mut root = dir("root", vec![leaf("big.txt", 90), leaf("tiny.txt", 10)]);
+        root.aggregated_size = aggregate_existing_children(&mut root, true);
+
+        collapse_below_threshold(&mut root, 20.0);
+
+        let children = root.children.as_ref().unwrap();
+        assert_eq!(children.len(), 2);
+        assert!(children.iter().any(|c| c.name == "big.txt"));
+        let others = children.iter().find(|c| c.name == "<others>").unwrap();
+        assert_eq!(others.node_type, "others");
+        assert_eq!(others.aggregated_size, 10);
+    }
+
+    /// Exercises `build_tree`'s `populate_children` (the non-cached path) end to end over a
+    /// real nested temp-dir tree, covering the arithmetic `aggregate_existing_children`'s
+    /// tests above cover for the cached/archive path: multi-level size summation, correct
+    /// `percent_of_parent`, and `sort_by_size` reordering.
+    #[test]
+    fn test_build_tree_aggregates_and_sorts_nested_directories() {
+        let root = env::temp_dir().join(format!(
+            "ponder_commands_test_{}_{}",
+            std::process::id(),
+            "build_tree_aggregation"
+        ));
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub/a.txt"), vec![0u8; 10]).unwrap();
+        fs::write(root.join("sub/b.txt"), vec![0u8; 20]).unwrap();
+        fs::write(root.join("big.txt"), vec![0u8; 100]).unwrap();
+
+        let mut node_count = 0;
+        let tree = build_tree(&root, &root, &mut node_count, None, true, None).unwrap();
+
+        assert_eq!(tree.aggregated_size, 130);
+
+        let names: Vec<&str> = tree
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["big.txt", "sub"]);
+
+        let sub = tree.children.as_ref().unwrap().iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!(sub.aggregated_size, 30);
+        assert_eq!(sub.percent_of_parent, (30.0 / 130.0) * 100.0);
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    /// Regression test for `read_text_file` silently handing back a clipped prefix of an
+    /// oversized file instead of signaling that it was truncated: a caller of this entry
+    /// point can't see `FileReadResult::truncated`, so exceeding `max_bytes` must be a hard
+    /// error, the way it was before `read_text_file` was refactored into a thin wrapper over
+    /// `read_text_file_range`.
+    #[test]
+    fn test_read_text_file_errors_on_truncation_instead_of_silently_clipping() {
+        let root = env::temp_dir().join(format!(
+            "ponder_commands_test_{}_{}",
+            std::process::id(),
+            "read_text_file_truncation"
+        ));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("big.txt"), vec![b'a'; 1000]).unwrap();
+        fs::write(root.join("small.txt"), "hello").unwrap();
+
+        let root_str = root.to_string_lossy().to_string();
+
+        let err = read_text_file(root_str.clone(), "big.txt".to_string(), Some(100)).unwrap_err();
+        assert!(err.contains("too large"));
+
+        let content = read_text_file(root_str, "small.txt".to_string(), Some(100)).unwrap();
+        assert_eq!(content, "hello");
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    /// The caller always reads one byte past `max_bytes` (see `read_text_file_range`'s
+    /// archive branch), so a member that keeps going past the requested window shows up
+    /// here as `bytes.len() > max_bytes` — that probe byte, not `declared_size`, is what
+    /// decides `truncated`, since a forged `declared_size` must never be trusted over what
+    /// was physically read (in either direction: understating or overstating the truth).
+    #[test]
+    fn test_read_window_from_archive_bytes_flags_truncation_when_probe_byte_is_present() {
+        let bytes = b"01234".to_vec(); // one byte past the max_bytes=4 window
+        let result = read_window_from_archive_bytes(&bytes, 4, 0, 4, 4).unwrap();
+        assert_eq!(result.content, "0123");
+        assert!(result.truncated);
+    }
+
+    /// A member that ends exactly at the requested window (no probe byte came back) must
+    /// not be reported as truncated just because the read happened to use all of its cap —
+    /// reaching real EOF within the cap is definitive proof there's nothing more, regardless
+    /// of what a forged `declared_size` claims either way.
+    #[test]
+    fn test_read_window_from_archive_bytes_not_truncated_when_member_ends_exactly_at_window() {
+        let bytes = b"0123".to_vec(); // exactly max_bytes=4, no probe byte
+        let result = read_window_from_archive_bytes(&bytes, 10_000, 0, 4, 4).unwrap();
+        assert_eq!(result.content, "0123");
+        assert_eq!(result.size_bytes, 4);
+        assert!(!result.truncated);
+    }
+
+    /// Regression test for a forged-small `declared_size` (the zip/tar-bomb scenario) not
+    /// being allowed to understate `size_bytes` below what the probe byte actually proved:
+    /// a member that claims size=4 but still has data past byte 4 must report a `size_bytes`
+    /// of at least `max_bytes + 1`, not the forged `declared_size`.
+    #[test]
+    fn test_read_window_from_archive_bytes_does_not_trust_declared_size_when_capped() {
+        let bytes = b"01234".to_vec(); // probe byte present past max_bytes=4
+        let result = read_window_from_archive_bytes(&bytes, 4, 0, 4, 4).unwrap();
+        assert_eq!(result.size_bytes, 5);
+        assert!(result.truncated);
+    }
 }
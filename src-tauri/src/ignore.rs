@@ -0,0 +1,290 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::to_relative_posix_path;
+
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+#[derive(Clone, Debug)]
+struct CompiledPattern {
+    glob: String,
+    /// Workspace-relative directory the ignore file lives in ("" for the root). A pattern
+    /// only ever matches paths inside this subtree, regardless of `anchored`.
+    base_rel: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl CompiledPattern {
+    fn parse(raw: &str, base_rel: &str) -> Option<CompiledPattern> {
+        let line = raw.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut pattern = line;
+        let negated = pattern.starts_with('!');
+        if negated {
+            pattern = &pattern[1..];
+        }
+
+        let dir_only = pattern.ends_with('/') && pattern.len() > 1;
+        if dir_only {
+            pattern = &pattern[..pattern.len() - 1];
+        }
+
+        let root_anchored = pattern.starts_with('/');
+        if root_anchored {
+            pattern = &pattern[1..];
+        }
+        let anchored = root_anchored || pattern.contains('/');
+
+        Some(CompiledPattern {
+            glob: pattern.to_string(),
+            base_rel: base_rel.to_string(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// `rel_path` is confined to this pattern's `base_rel` subtree first (a nested
+    /// `.gitignore`'s patterns never reach outside the directory it lives in), then matched
+    /// against the remaining path below `base_rel` — as a whole for an anchored pattern, or
+    /// at any depth below it for an unanchored one.
+    fn matches(&self, rel_path: &str) -> bool {
+        let suffix = match self.base_rel.as_str() {
+            "" => rel_path,
+            base_rel if rel_path == base_rel => "",
+            base_rel => match rel_path.strip_prefix(base_rel).and_then(|r| r.strip_prefix('/')) {
+                Some(rest) => rest,
+                None => return false,
+            },
+        };
+
+        if self.anchored {
+            return glob_match(&self.glob, suffix);
+        }
+
+        if glob_match(&self.glob, suffix) {
+            return true;
+        }
+
+        for (i, c) in suffix.char_indices() {
+            if c == '/' && glob_match(&self.glob, &suffix[i + 1..]) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Matches a `.gitignore`-style glob (`*`, `**`, `?`) against a relative POSIX path.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    glob_match_bytes(pattern.as_bytes(), text.as_bytes())
+}
+
+fn glob_match_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    if pattern.first() == Some(&b'*') && pattern.get(1) == Some(&b'*') {
+        let mut rest = &pattern[2..];
+        if rest.first() == Some(&b'/') {
+            rest = &rest[1..];
+        }
+        if glob_match_bytes(rest, text) {
+            return true;
+        }
+        return match text.first() {
+            Some(_) => glob_match_bytes(pattern, &text[1..]),
+            None => false,
+        };
+    }
+
+    match (pattern.first(), text.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            if glob_match_bytes(&pattern[1..], text) {
+                return true;
+            }
+            match text.first() {
+                Some(&c) if c != b'/' => glob_match_bytes(pattern, &text[1..]),
+                _ => false,
+            }
+        }
+        (Some(b'?'), Some(&c)) if c != b'/' => glob_match_bytes(&pattern[1..], &text[1..]),
+        (Some(&p), Some(&c)) if p == c => glob_match_bytes(&pattern[1..], &text[1..]),
+        _ => false,
+    }
+}
+
+/// Loads and parses one ignore file, appending its patterns to `out` and following
+/// `%include <path>` directives (relative to the workspace root) up to `MAX_INCLUDE_DEPTH`
+/// to guard against include cycles.
+fn load_ignore_file(
+    path: &Path,
+    root: &Path,
+    base_rel: &str,
+    out: &mut Vec<CompiledPattern>,
+    visited: &mut HashSet<PathBuf>,
+    depth: usize,
+) {
+    if depth > MAX_INCLUDE_DEPTH {
+        return;
+    }
+
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(canonical) {
+        return;
+    }
+
+    let Ok(contents) = fs::read_to_string(path) else {
+        return;
+    };
+
+    for line in contents.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix("%include ") {
+            let include_path = root.join(rest.trim().replace('/', std::path::MAIN_SEPARATOR_STR));
+            load_ignore_file(&include_path, root, base_rel, out, visited, depth + 1);
+            continue;
+        }
+
+        if let Some(compiled) = CompiledPattern::parse(line, base_rel) {
+            out.push(compiled);
+        }
+    }
+}
+
+/// Layered ignore-pattern engine for [`crate::commands::list_tree`], modeled on a
+/// `.gitignore`/`.ponderignore` file at the workspace root with optional per-directory
+/// overrides. Patterns are evaluated in order, last match wins, so a later `!pattern`
+/// re-includes a path excluded by an earlier one.
+pub struct IgnoreEngine {
+    root: PathBuf,
+    dir_patterns: RefCell<HashMap<PathBuf, Vec<CompiledPattern>>>,
+}
+
+impl IgnoreEngine {
+    pub fn new(root: &Path) -> Self {
+        let root = root.to_path_buf();
+        let mut patterns = Vec::new();
+        for name in [".ponderignore", ".gitignore"] {
+            load_ignore_file(&root.join(name), &root, "", &mut patterns, &mut HashSet::new(), 0);
+        }
+
+        let mut dir_patterns = HashMap::new();
+        dir_patterns.insert(root.clone(), patterns);
+        IgnoreEngine {
+            root,
+            dir_patterns: RefCell::new(dir_patterns),
+        }
+    }
+
+    fn patterns_for_dir(&self, dir: &Path) -> Vec<CompiledPattern> {
+        if let Some(patterns) = self.dir_patterns.borrow().get(dir) {
+            return patterns.clone();
+        }
+
+        let parent = dir.parent().unwrap_or(dir);
+        let mut patterns = if parent == dir {
+            Vec::new()
+        } else {
+            self.patterns_for_dir(parent)
+        };
+
+        let rel = to_relative_posix_path(dir, &self.root);
+        for name in [".ponderignore", ".gitignore"] {
+            load_ignore_file(&dir.join(name), &self.root, &rel, &mut patterns, &mut HashSet::new(), 0);
+        }
+
+        self.dir_patterns
+            .borrow_mut()
+            .insert(dir.to_path_buf(), patterns.clone());
+        patterns
+    }
+
+    /// Returns whether `entry_path` should be excluded from `list_tree`'s walk.
+    pub fn is_ignored(&self, entry_path: &Path, is_dir: bool) -> bool {
+        let parent = entry_path.parent().unwrap_or(&self.root);
+        let patterns = self.patterns_for_dir(parent);
+        let rel = to_relative_posix_path(entry_path, &self.root);
+
+        let mut ignored = false;
+        for pattern in &patterns {
+            if pattern.dir_only && !is_dir {
+                continue;
+            }
+            if pattern.matches(&rel) {
+                ignored = !pattern.negated;
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match_wildcards() {
+        assert!(glob_match("*.log", "debug.log"));
+        assert!(!glob_match("*.log", "debug.log.txt"));
+        assert!(glob_match("src/**/*.rs", "src/a/b/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("a?c", "abc"));
+    }
+
+    #[test]
+    fn test_negation_reincludes_path() {
+        let patterns = vec![
+            CompiledPattern::parse("*.log", "").unwrap(),
+            CompiledPattern::parse("!keep.log", "").unwrap(),
+        ];
+
+        let mut ignored = false;
+        for p in &patterns {
+            if p.matches("keep.log") {
+                ignored = !p.negated;
+            }
+        }
+        assert!(!ignored);
+
+        let mut ignored = false;
+        for p in &patterns {
+            if p.matches("other.log") {
+                ignored = !p.negated;
+            }
+        }
+        assert!(ignored);
+    }
+
+    /// Regression test for a nested `.gitignore`'s unanchored pattern leaking outside the
+    /// directory it lives in: `src/.gitignore`'s `*.log` must ignore files anywhere below
+    /// `src/`, but never a same-named path under an unrelated directory like `vendor/src/`.
+    #[test]
+    fn test_nested_ignore_file_pattern_is_confined_to_its_subtree() {
+        let root = std::env::temp_dir().join(format!(
+            "ponder_ignore_test_{}_{}",
+            std::process::id(),
+            "nested_scope"
+        ));
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("vendor/src")).unwrap();
+        fs::write(root.join("src/.gitignore"), "*.log\n").unwrap();
+        fs::write(root.join("src/app.log"), "").unwrap();
+        fs::write(root.join("src/nested/app.log"), "").unwrap();
+        fs::write(root.join("vendor/src/app.log"), "").unwrap();
+
+        let engine = IgnoreEngine::new(&root);
+
+        assert!(engine.is_ignored(&root.join("src/app.log"), false));
+        assert!(engine.is_ignored(&root.join("src/nested/app.log"), false));
+        assert!(!engine.is_ignored(&root.join("vendor/src/app.log"), false));
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
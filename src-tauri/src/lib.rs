@@ -1,7 +1,11 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+mod archive;
+mod bundle;
+mod cache;
 mod commands;
+mod ignore;
 
-use commands::{list_tree, read_text_file};
+use commands::{bundle_workspace, list_tree, read_from_bundle, read_text_file, read_text_file_range};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -9,7 +13,13 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
-        .invoke_handler(tauri::generate_handler![list_tree, read_text_file])
+        .invoke_handler(tauri::generate_handler![
+            list_tree,
+            read_text_file,
+            read_text_file_range,
+            bundle_workspace,
+            read_from_bundle
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }